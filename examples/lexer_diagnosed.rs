@@ -0,0 +1,95 @@
+//! Shows off `Diagnosed`/`into_iter_diagnosed`: unlike `lexer_fn.rs`,
+//! a malformed float like `"4."` is still lexed as a `Float` token,
+//! just with a diagnostic attached pointing out the missing
+//! fractional digits, instead of collapsing into an opaque `Error`.
+
+use rowan_tools::{
+    lexer::{self, Diagnosed, Error},
+    rowan::TextUnit,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum TokenKind {
+    // Meta
+    Error,
+    Whitespace,
+
+    // Operators
+    Add,
+
+    // Types
+    Float,
+    Integer,
+}
+
+fn lex(remaining: &str) -> Option<(TokenKind, TextUnit, Vec<(Error, TextUnit)>)> {
+    lexer::wrap(remaining, |state| match state.peek().unwrap() {
+        c if c.is_whitespace() => {
+            state.take_while(char::is_whitespace);
+            Diagnosed::new(TokenKind::Whitespace)
+        },
+        c if c == '.' || c.is_digit(10) => {
+            let leading = state.take_while(|c| c.is_digit(10));
+            if state.take(".").any() {
+                let dot_offset = leading.bytes() + TextUnit::of_str(".");
+                let trailing = state.take_while(|c| c.is_digit(10));
+                if trailing.any() {
+                    Diagnosed::new(TokenKind::Float)
+                } else {
+                    Diagnosed::new(TokenKind::Float).with_diagnostic(
+                        Error::Diagnostic {
+                            message: "missing fractional digits".to_string(),
+                        },
+                        dot_offset,
+                    )
+                }
+            } else {
+                Diagnosed::new(TokenKind::Integer)
+            }
+        },
+        '+' => {
+            state.bump();
+            Diagnosed::new(TokenKind::Add)
+        },
+        _ => {
+            state.bump();
+            Diagnosed::new(TokenKind::Error)
+        },
+    })
+}
+
+fn tokenize<'i>(
+    input: &'i str,
+    diagnostics: &'i mut Vec<(Error, TextUnit)>,
+) -> impl Iterator<Item = (TokenKind, &'i str)> + 'i {
+    lexer::string_slices(input, lexer::into_iter_diagnosed(input, diagnostics, lex))
+}
+
+#[rustfmt::skip]
+fn main() {
+    let mut diagnostics = Vec::new();
+    let lexer = tokenize("1 + 2.3 + 4. + .5", &mut diagnostics);
+    let tokens: Vec<_> = lexer.collect();
+
+    assert_eq!(tokens, vec![
+        (TokenKind::Integer,    "1"),
+        (TokenKind::Whitespace, " "),
+        (TokenKind::Add,        "+"),
+        (TokenKind::Whitespace, " "),
+        (TokenKind::Float,      "2.3"),
+        (TokenKind::Whitespace, " "),
+        (TokenKind::Add,        "+"),
+        (TokenKind::Whitespace, " "),
+        (TokenKind::Float,      "4."),
+        (TokenKind::Whitespace, " "),
+        (TokenKind::Add,        "+"),
+        (TokenKind::Whitespace, " "),
+        (TokenKind::Float,      ".5"),
+    ]);
+
+    assert_eq!(diagnostics.len(), 1);
+    match &diagnostics[0].0 {
+        Error::Diagnostic { message, .. } => assert_eq!(message, "missing fractional digits"),
+        _ => panic!("expected a Diagnostic error"),
+    }
+}
@@ -0,0 +1,90 @@
+//! Demonstrates `ModeStack`/`into_iter_stateful`: lexing a block
+//! comment (`/* ... */`) needs a different rule set than ordinary
+//! code until the matching `*/` is seen, which is exactly the kind of
+//! context switch a mode stack is for.
+
+use rowan_tools::{
+    lexer::{self, Error, ModeStack},
+    rowan::TextUnit,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+enum TokenKind {
+    // Meta
+    Error,
+    Whitespace,
+
+    // Code mode
+    Integer,
+
+    // Comment mode
+    CommentStart,
+    CommentText,
+    CommentEnd,
+}
+impl From<Error> for TokenKind {
+    fn from(_error: Error) -> Self {
+        Self::Error
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Code,
+    Comment,
+}
+
+fn lex(remaining: &str, modes: &mut ModeStack<Mode>) -> Option<(TokenKind, TextUnit)> {
+    lexer::wrap(remaining, |state| match *modes.current() {
+        Mode::Code => match state.peek().unwrap() {
+            c if c.is_whitespace() => {
+                state.take_while(char::is_whitespace);
+                Ok(TokenKind::Whitespace)
+            },
+            c if c.is_digit(10) => {
+                state.take_while(|c| c.is_digit(10));
+                Ok(TokenKind::Integer)
+            },
+            '/' if state.starts_with("/*") => {
+                state.consume(TextUnit::of_str("/*"));
+                modes.push(Mode::Comment);
+                Ok(TokenKind::CommentStart)
+            },
+            _ => {
+                state.bump();
+                Err(Error::UnexpectedInput)
+            },
+        },
+        Mode::Comment => {
+            if state.starts_with("*/") {
+                state.consume(TextUnit::of_str("*/"));
+                modes.pop();
+                Ok(TokenKind::CommentEnd)
+            } else {
+                // Consume everything up to the closing `*/` (or to
+                // the end of input, if it's never closed).
+                while !state.remaining().is_empty() && !state.starts_with("*/") {
+                    state.bump();
+                }
+                Ok(TokenKind::CommentText)
+            }
+        },
+    })
+}
+
+fn tokenize(input: &'_ str) -> impl Iterator<Item = (TokenKind, &'_ str)> + '_ {
+    lexer::string_slices(input, lexer::into_iter_stateful(input, Mode::Code, lex))
+}
+
+#[rustfmt::skip]
+fn main() {
+    let mut lexer = tokenize("1 /* hi */ 2");
+    assert_eq!(lexer.next(), Some((TokenKind::Integer,      "1")));
+    assert_eq!(lexer.next(), Some((TokenKind::Whitespace,   " ")));
+    assert_eq!(lexer.next(), Some((TokenKind::CommentStart, "/*")));
+    assert_eq!(lexer.next(), Some((TokenKind::CommentText,  " hi ")));
+    assert_eq!(lexer.next(), Some((TokenKind::CommentEnd,   "*/")));
+    assert_eq!(lexer.next(), Some((TokenKind::Whitespace,   " ")));
+    assert_eq!(lexer.next(), Some((TokenKind::Integer,      "2")));
+    assert_eq!(lexer.next(), None);
+}
@@ -0,0 +1,85 @@
+//! Exercises `State::line`/`column`/`position` and `wrap_positioned`:
+//! lexes a string containing a newline and a multi-byte character and
+//! asserts the resulting positions, rather than just eyeballing the
+//! chars-vs-bytes counting and column-reset-on-newline logic.
+
+use rowan_tools::{
+    lexer::{self, Error, Position, State},
+    rowan::TextUnit,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+enum TokenKind {
+    // Meta
+    Error,
+    Whitespace,
+
+    // Types
+    Word,
+}
+impl From<Error> for TokenKind {
+    fn from(_error: Error) -> Self {
+        Self::Error
+    }
+}
+
+fn lex(state: State<'_>) -> Option<((TokenKind, TextUnit), Position)> {
+    lexer::wrap_positioned(state, |state| match state.peek().unwrap() {
+        c if c.is_whitespace() => {
+            state.take_while(char::is_whitespace);
+            Ok(TokenKind::Whitespace)
+        },
+        _ => {
+            state.take_while(|c| !c.is_whitespace());
+            Ok(TokenKind::Word)
+        },
+    })
+}
+
+fn tokenize(input: &str) -> impl Iterator<Item = ((TokenKind, TextUnit), Position)> + '_ {
+    // Carry one `State` across tokens (rather than a bare `&str`) so
+    // its line/column keep counting from where the last token ended.
+    let mut state = State::new(input);
+    std::iter::from_fn(move || {
+        let (token, position) = lex(state)?;
+        state.consume(token.1);
+        Some((token, position))
+    })
+}
+
+fn main() {
+    // `consume` (and `bump`/`take`/`take_while` built on it) tracks
+    // 1-based line/column as it eats across a newline and a
+    // multi-byte character ('ä' is 2 bytes in UTF-8 but a single
+    // column).
+    let mut state = State::new("foo\nbär");
+    assert_eq!(state.position(), (1, 1));
+    state.take_while(|c| c != '\n'); // "foo"
+    assert_eq!(state.position(), (1, 4));
+    state.bump(); // the newline itself
+    assert_eq!(state.position(), (2, 1));
+    assert_eq!(state.line(), 2);
+    assert_eq!(state.column(), 1);
+    state.bump(); // 'b'
+    assert_eq!(state.position(), (2, 2));
+    state.bump(); // 'ä'
+    assert_eq!(state.position(), (2, 3));
+
+    // `wrap_positioned` surfaces the position a token started at
+    // alongside its usual `Attach` output.
+    let mut lexer = tokenize("foo\nbär");
+
+    let (token, position) = lexer.next().expect("a Word token");
+    assert_eq!(token.0, TokenKind::Word);
+    assert_eq!((position.line(), position.column()), (1, 1));
+
+    let (token, position) = lexer.next().expect("a Whitespace token");
+    assert_eq!(token.0, TokenKind::Whitespace);
+    assert_eq!((position.line(), position.column()), (1, 4));
+
+    let (token, position) = lexer.next().expect("a Word token");
+    assert_eq!(token.0, TokenKind::Word);
+    assert_eq!((position.line(), position.column()), (2, 1));
+
+    assert_eq!(lexer.next(), None);
+}
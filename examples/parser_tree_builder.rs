@@ -0,0 +1,150 @@
+//! A tiny recursive-descent parser for left-associative `+` chains
+//! (e.g. `"1 + 2 + 3"`), showing `parser::TreeBuilder` and
+//! `lexer::into_iter_spanned` working together: absolute `Span`s are
+//! used to slice out token text, `start_node_at`/`checkpoint` wrap an
+//! already-emitted left-hand side once a following `+` is seen, and
+//! the trailing `Eof` sentinel tells the parser loop when to stop.
+
+use std::iter::Peekable;
+
+use rowan_tools::{
+    lexer::{self, Error, Eof, Span, Token},
+    parser::TreeBuilder,
+    rowan::{GreenNode, SyntaxKind, TextUnit},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u16)]
+enum TokenKind {
+    // Meta
+    Error,
+    Whitespace,
+    Eof,
+
+    // Tokens
+    Add,
+    Integer,
+
+    // Nodes
+    BinExpr,
+    Root,
+}
+impl From<Error> for TokenKind {
+    fn from(_error: Error) -> Self {
+        Self::Error
+    }
+}
+impl From<TokenKind> for SyntaxKind {
+    fn from(kind: TokenKind) -> Self {
+        SyntaxKind(kind as u16)
+    }
+}
+
+/// A lexed token paired with its length, as required by `Token`, with
+/// an explicit `Eof` sentinel as required by `Eof`.
+struct Tok(TokenKind, TextUnit);
+impl Token for Tok {
+    fn len(&self) -> TextUnit {
+        self.1
+    }
+}
+impl Eof for Tok {
+    fn eof() -> Self {
+        Tok(TokenKind::Eof, TextUnit::from(0))
+    }
+}
+impl From<Tok> for TokenKind {
+    fn from(tok: Tok) -> Self {
+        tok.0
+    }
+}
+
+fn lex(remaining: &str) -> Option<Tok> {
+    let (kind, len) = lexer::wrap(remaining, |state| match state.peek().unwrap() {
+        c if c.is_whitespace() => {
+            state.take_while(char::is_whitespace);
+            Ok(TokenKind::Whitespace)
+        },
+        c if c.is_digit(10) => {
+            state.take_while(|c| c.is_digit(10));
+            Ok(TokenKind::Integer)
+        },
+        '+' => {
+            state.bump();
+            Ok(TokenKind::Add)
+        },
+        _ => {
+            state.bump();
+            Err(Error::UnexpectedInput)
+        },
+    })?;
+    Some(Tok(kind, len))
+}
+
+fn text<'i>(input: &'i str, span: Span) -> &'i str {
+    &input[span.start().to_usize()..span.end().to_usize()]
+}
+
+fn is_whitespace(tok: &Tok) -> bool {
+    tok.0 == TokenKind::Whitespace
+}
+
+fn parse_integer<I>(input: &str, tokens: &mut Peekable<I>, builder: &mut TreeBuilder<TokenKind>)
+where
+    I: Iterator<Item = (Tok, Span)>,
+{
+    match tokens.next() {
+        Some((tok, span)) if tok.0 == TokenKind::Integer => {
+            builder.token(tok.0, text(input, span));
+        },
+        other => panic!("expected an integer, got {:?}", other.map(|(tok, _)| tok.0)),
+    }
+}
+
+/// `expr := integer (ws* '+' ws* integer)*`, left-associative.
+fn parse_expr<I>(input: &str, tokens: &mut Peekable<I>, builder: &mut TreeBuilder<TokenKind>)
+where
+    I: Iterator<Item = (Tok, Span)>,
+{
+    builder.skip_trivia(input, tokens, is_whitespace);
+    let checkpoint = builder.checkpoint();
+    parse_integer(input, tokens, builder);
+    loop {
+        builder.skip_trivia(input, tokens, is_whitespace);
+        match tokens.peek() {
+            Some((tok, _)) if tok.0 == TokenKind::Add => {
+                let (tok, span) = tokens.next().expect("just peeked");
+                builder.start_node_at(checkpoint, TokenKind::BinExpr);
+                builder.token(tok.0, text(input, span));
+                builder.skip_trivia(input, tokens, is_whitespace);
+                parse_integer(input, tokens, builder);
+                builder.finish_node();
+            },
+            _ => break,
+        }
+    }
+}
+
+fn parse(input: &str) -> GreenNode {
+    let mut tokens = lexer::into_iter_spanned(input, lex).peekable();
+    let mut builder = TreeBuilder::<TokenKind>::new();
+    builder.start_node(TokenKind::Root);
+    parse_expr(input, &mut tokens, &mut builder);
+    builder.skip_trivia(input, &mut tokens, is_whitespace);
+    match tokens.next() {
+        Some((tok, _)) if tok.0 == TokenKind::Eof => {},
+        other => panic!("expected eof, got {:?}", other.map(|(tok, _)| tok.0)),
+    }
+    builder.finish_node();
+    builder.finish()
+}
+
+fn main() {
+    let input = "1 + 2 + 3";
+    let tree = parse(input);
+
+    assert_eq!(tree.kind(), TokenKind::Root.into());
+    // The tree is lossless: every byte of the input, including
+    // whitespace, ended up attached somewhere in it.
+    assert_eq!(tree.text_len(), TextUnit::of_str(input));
+}
@@ -0,0 +1,57 @@
+//! Exercises the declarative pattern-matcher combinators in
+//! `lexer::{alt, char_range, literal, many1, opt, pred, seq}`, showing
+//! how they compose into bigger rules without a macro/codegen stage.
+
+use rowan_tools::lexer::{alt, char_range, literal, many1, opt, pred, seq, Pattern, State};
+
+fn matched_text<'a>(input: &'a str, pattern: &dyn Pattern) -> Option<&'a str> {
+    let mut state = State::new(input);
+    let consumed = pattern.try_match(&mut state)?;
+    Some(&input[..consumed.bytes().to_usize()])
+}
+
+#[rustfmt::skip]
+fn main() {
+    // `many1` matches one or more repetitions, failing cleanly
+    // (returning `None`) if there isn't even one.
+    let digits = many1(char_range('0'..='9'));
+    assert_eq!(matched_text("123abc", &digits), Some("123"));
+    assert_eq!(matched_text("abc", &digits), None);
+
+    // `literal` matches an exact string.
+    assert_eq!(matched_text("+1", &literal("+")), Some("+"));
+    assert_eq!(matched_text("-1", &literal("+")), None);
+
+    // `pred` matches a single character meeting a predicate.
+    assert_eq!(matched_text("  x", &pred(char::is_whitespace)), Some(" "));
+
+    // `seq` matches every pattern in order, rolling back as a unit if
+    // any of them fails to match -- a required prefix, a required
+    // suffix.
+    let dot = literal(".");
+    let fraction_digits = many1(char_range('0'..='9'));
+    let fraction_patterns: [&dyn Pattern; 2] = [&dot, &fraction_digits];
+    let fraction = seq(&fraction_patterns);
+    assert_eq!(matched_text(".45", &fraction), Some(".45"));
+    assert_eq!(matched_text(".", &fraction), None);
+    assert_eq!(matched_text("45", &fraction), None);
+
+    // `opt` never fails, and a zero-width optional match doesn't
+    // break a surrounding `seq`: digits, optionally followed by a
+    // fraction -- the canonical "integer or float" shape.
+    let int_digits = many1(char_range('0'..='9'));
+    let optional_fraction = opt(seq(&fraction_patterns));
+    let number_patterns: [&dyn Pattern; 2] = [&int_digits, &optional_fraction];
+    let number = seq(&number_patterns);
+    assert_eq!(matched_text("123.45", &number), Some("123.45"));
+    assert_eq!(matched_text("123", &number), Some("123"));
+
+    // `alt` tries every alternative and commits the longest match.
+    let add = literal("+");
+    let sub = literal("-");
+    let operator_patterns: [&dyn Pattern; 2] = [&add, &sub];
+    let operator = alt(&operator_patterns);
+    assert_eq!(matched_text("+1", &operator), Some("+"));
+    assert_eq!(matched_text("-1", &operator), Some("-"));
+    assert_eq!(matched_text("1", &operator), None);
+}
@@ -1,6 +1,7 @@
 use super::Error;
 
 use std::iter::{FromIterator, IntoIterator};
+use std::ops::Add;
 
 use rowan::TextUnit;
 
@@ -50,6 +51,18 @@ impl Consumed {
         }
     }
 }
+impl Add for Consumed {
+    type Output = Self;
+
+    /// Combine two consumed amounts, as if they'd been consumed one
+    /// after the other.
+    fn add(self, other: Self) -> Self {
+        Self {
+            chars: self.chars + other.chars,
+            bytes: self.bytes + other.bytes,
+        }
+    }
+}
 impl FromIterator<char> for Consumed {
     fn from_iter<T>(iter: T) -> Self
     where
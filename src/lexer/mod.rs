@@ -8,16 +8,31 @@ use std::{
 };
 
 mod consumed;
+mod mode_stack;
+mod pattern;
 
 pub use self::consumed::Consumed;
+pub use self::mode_stack::ModeStack;
+pub use self::pattern::{
+    alt, char_range, literal, many1, opt, pred, seq, Alt, CharRange, Literal, Many1, Opt, Pattern,
+    Pred, Seq,
+};
 
 /// An error that can occur when lexing
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum Error {
     /// An invalid token was detected
     UnexpectedInput,
     /// End of file was reached mid-token
     UnexpectedEOF,
+    /// A non-fatal problem with an otherwise valid token, e.g. a
+    /// missing fractional digit after a decimal point. Unlike the
+    /// other variants, this doesn't abort the token it was found in;
+    /// see `Diagnosed`.
+    Diagnostic {
+        /// A human-readable description of the problem.
+        message: String,
+    },
 }
 
 /// Defines what a token is, a simple enum token kind complete with
@@ -32,16 +47,123 @@ impl<T> Token for (T, TextUnit) {
         self.1
     }
 }
+impl<T> Token for (T, TextUnit, Vec<(Error, TextUnit)>) {
+    fn len(&self) -> TextUnit {
+        self.1
+    }
+}
+
+/// Splits a token produced by a `Diagnosed`-based lexer function into
+/// the plain `(T, TextUnit)` token `into_iter_diagnosed` yields and
+/// the diagnostics collected alongside it, so that function can stay
+/// generic over any such token type instead of hardcoding the
+/// `(T, TextUnit, Vec<(Error, TextUnit)>)` triple.
+pub trait IntoDiagnostics {
+    /// The plain token left once diagnostics are split off.
+    type Token;
+
+    /// Split this token into the plain token and its diagnostics.
+    fn into_diagnostics(self) -> (Self::Token, Vec<(Error, TextUnit)>);
+}
+impl<T> IntoDiagnostics for (T, TextUnit, Vec<(Error, TextUnit)>) {
+    type Token = (T, TextUnit);
+
+    fn into_diagnostics(self) -> (Self::Token, Vec<(Error, TextUnit)>) {
+        ((self.0, self.1), self.2)
+    }
+}
+
+/// Defines an explicit end-of-file marker for a token type, so
+/// `into_iter_spanned` can emit one once the real input is exhausted.
+pub trait Eof {
+    /// Construct the end-of-file sentinel for this token type.
+    fn eof() -> Self;
+}
+
+/// An absolute `[start, end)` byte span within the original input, as
+/// opposed to the relative `TextUnit` lengths used elsewhere in this
+/// module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    start: TextUnit,
+    end: TextUnit,
+}
+impl Span {
+    /// Construct a span covering `[start, end)`.
+    pub fn new(start: TextUnit, end: TextUnit) -> Self {
+        Self {
+            start,
+            end,
+        }
+    }
+    /// The byte offset this span starts at.
+    pub fn start(&self) -> TextUnit {
+        self.start
+    }
+    /// The byte offset this span ends at, exclusive.
+    pub fn end(&self) -> TextUnit {
+        self.end
+    }
+    /// The length of this span.
+    pub fn len(&self) -> TextUnit {
+        self.end - self.start
+    }
+    /// Returns true if this span covers no bytes at all, as is the
+    /// case for the `Eof` sentinel emitted by `into_iter_spanned`.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// A token successfully produced, plus zero or more non-fatal
+/// diagnostics collected while producing it. Unlike a bare
+/// `Result<T, Error>`, wrapping a token in `Diagnosed` doesn't abort
+/// lexing on a problem: the token is still emitted, and the
+/// diagnostics are just carried alongside it so recoverable issues
+/// (e.g. `"4."` missing its fractional digits) can still be reported
+/// without collapsing the token into an opaque `Error`.
+#[derive(Debug, Clone)]
+pub struct Diagnosed<T> {
+    token: T,
+    diagnostics: Vec<(Error, TextUnit)>,
+}
+impl<T> Diagnosed<T> {
+    /// Wrap a token with no diagnostics attached.
+    pub fn new(token: T) -> Self {
+        Self {
+            token,
+            diagnostics: Vec::new(),
+        }
+    }
+    /// Attach a diagnostic found `span` into this token, in addition
+    /// to any already attached.
+    pub fn with_diagnostic(mut self, error: Error, span: TextUnit) -> Self {
+        self.diagnostics.push((error, span));
+        self
+    }
+    /// The diagnostics collected so far.
+    pub fn diagnostics(&self) -> &[(Error, TextUnit)] {
+        &self.diagnostics
+    }
+}
+impl<T> From<T> for Diagnosed<T> {
+    fn from(token: T) -> Self {
+        Self::new(token)
+    }
+}
 
 /// Defines how to attach text length to a token type. This is first
 /// used by `wrap` to attach the length of the text consumed, and then
 /// the higher-level API `into_iter` converts the lengths to
 /// subslices.
 ///
-/// You shouldn't need to implement this yourself in most cases,
-/// there's a default implementation for `Result<T, Error>` where `T`
-/// implements `From<Error>`, that results in a simple `(T, TextUnit)`
-/// tuple.
+/// You shouldn't need to implement this yourself in most cases.
+/// There's a default implementation for `Result<T, Error>` where `T`
+/// implements `From<Error>`, resulting in a simple `(T, TextUnit)`
+/// tuple, and one for `Diagnosed<T>`, resulting in a
+/// `(T, TextUnit, Vec<(Error, TextUnit)>)` triple for lexers that want
+/// to keep lexing past recoverable problems instead of giving up on
+/// the token.
 pub trait Attach {
     /// The output type
     type Output;
@@ -61,13 +183,36 @@ impl<T: From<Error>> Attach for Result<T, Error> {
         }
     }
 }
+impl<T> Attach for Diagnosed<T> {
+    type Output = (T, TextUnit, Vec<(Error, TextUnit)>);
+
+    fn attach(self, len: TextUnit) -> Self::Output {
+        (self.token, len, self.diagnostics)
+    }
+}
 
 /// A lexer state defines where in the input the lexer is. Basically a
 /// fancy wrapper around `&str` that adds a few convenience functions
 /// that slices away chunks of the string.
-#[derive(Default, Copy, Clone)]
+///
+/// Besides the remaining input, a `State` also tracks the current
+/// 1-based line and column (in chars, not bytes) so tokens can be
+/// reported with a human-readable position instead of just a byte
+/// offset.
+#[derive(Copy, Clone)]
 pub struct State<'a> {
     input: &'a str,
+    line: usize,
+    column: usize,
+}
+impl<'a> Default for State<'a> {
+    fn default() -> Self {
+        Self {
+            input: "",
+            line: 1,
+            column: 1,
+        }
+    }
 }
 impl<'a> fmt::Debug for State<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -80,10 +225,13 @@ impl<'a> From<&'a str> for State<'a> {
     }
 }
 impl<'a> State<'a> {
-    /// Construct a new state ready to eat away `input`
+    /// Construct a new state ready to eat away `input`, starting at
+    /// line 1, column 1.
     pub fn new(input: &'a str) -> Self {
         Self {
             input,
+            line: 1,
+            column: 1,
         }
     }
 
@@ -96,12 +244,72 @@ impl<'a> State<'a> {
     pub fn peek(self) -> Result<char, Error> {
         self.remaining().chars().next().ok_or(Error::UnexpectedEOF)
     }
+    /// Return the `n`th character (0-indexed) of the remaining string
+    /// to be lexed without consuming anything, or an unexpected eof
+    /// error if there aren't that many characters left.
+    pub fn peek_nth(self, n: usize) -> Result<char, Error> {
+        self.remaining().chars().nth(n).ok_or(Error::UnexpectedEOF)
+    }
+    /// Returns true if the remaining input starts with `s`, without
+    /// consuming anything.
+    pub fn starts_with(self, s: &str) -> bool {
+        self.remaining().starts_with(s)
+    }
+
+    /// Save the current position so it can later be restored with
+    /// `restore`. Since `State` is `Copy` (the remaining `&str` plus
+    /// line and column), this is simply a copy of `self`.
+    pub fn checkpoint(&self) -> Self {
+        *self
+    }
+    /// Roll back to a previously saved `checkpoint`, undoing any
+    /// consuming done in between. Useful for tokens that need to
+    /// speculatively try a branch and cleanly back out instead of
+    /// erroring.
+    ///
+    /// ### Panics
+    ///
+    /// In debug builds, panics if `checkpoint` didn't originate from
+    /// this same input, or if it's actually ahead of the current
+    /// position.
+    pub fn restore(&mut self, checkpoint: Self) {
+        let cur_ptr = self.input.as_ptr() as usize;
+        let chk_ptr = checkpoint.input.as_ptr() as usize;
+        debug_assert!(
+            chk_ptr <= cur_ptr && cur_ptr <= chk_ptr + checkpoint.input.len(),
+            "checkpoint must originate from the same buffer and not be ahead of the current position"
+        );
+        *self = checkpoint;
+    }
+
+    /// Return the current 1-based line number.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+    /// Return the current 1-based column number, counted in chars.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+    /// Return the current `(line, column)`, both 1-based. Equivalent
+    /// to calling `line()` and `column()` separately.
+    pub fn position(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
 
     /// Consume a certain amount of bytes. This will rightfully panic
     /// if you use a offset that breaks between code points, or if
     /// it's outside the string.
     pub fn consume(&mut self, len: TextUnit) {
-        self.input = &self.input[len.to_usize()..];
+        let (consumed, rest) = self.input.split_at(len.to_usize());
+        for c in consumed.chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.input = rest;
     }
     /// Consume the first character, panicking if called at the end of
     /// input (so always peek first)
@@ -158,6 +366,24 @@ impl<'a> Sub for State<'a> {
     }
 }
 
+/// A 1-based line/column pair recording where in the source a token
+/// started. Columns are counted in chars, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    line: usize,
+    column: usize,
+}
+impl Position {
+    /// Return the 1-based line number.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+    /// Return the 1-based column number.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
 /// Wrap your core lexer function with some boilerplate.
 ///
 /// 1. Return `None` if the string is empty.
@@ -188,6 +414,23 @@ where
     Some(output.attach(len))
 }
 
+/// Like `wrap`, but additionally returns the `Position` the token
+/// started at, so callers that care about diagnostics don't have to
+/// track line/column alongside the lexer state themselves.
+pub fn wrap_positioned<'a, F, R, S>(s: S, f: F) -> Option<(R::Output, Position)>
+where
+    F: FnOnce(&mut State) -> R,
+    R: Attach,
+    S: Into<State<'a>>,
+{
+    let start: State<'a> = s.into();
+    let position = Position {
+        line: start.line(),
+        column: start.column(),
+    };
+    wrap(start, f).map(|output| (output, position))
+}
+
 /// Lex a function repeatedly until it returns `None`. This may not
 /// fit the needs of everyone, so be prepared to re-implement it if
 /// needed. Luckily, it's simple.
@@ -210,6 +453,103 @@ where
     })
 }
 
+/// Like `into_iter`, but threads a `ModeStack<M>` through the lexer
+/// function so it can switch rule sets based on context (nested
+/// languages, string interpolation, comments, ...) instead of being
+/// limited to `into_iter`'s stateless-per-token model.
+///
+/// 1. Take a token from the remaining string, handing the lexer
+///    function the mode stack alongside it.
+/// 2. Exit if the previous step returned `None`.
+/// 3. Advance string with the taken length.
+/// 4. Repeat everything since step 1.
+pub fn into_iter_stateful<'a, F, T, M>(
+    input: &'a str,
+    initial_mode: M,
+    mut f: F,
+) -> impl Iterator<Item = T> + 'a
+where
+    T: Token,
+    F: (FnMut(&str, &mut ModeStack<M>) -> Option<T>) + 'a,
+    M: 'a,
+{
+    let mut remaining = input;
+    let mut modes = ModeStack::new(initial_mode);
+    iter::from_fn(move || {
+        let token = f(remaining, &mut modes)?;
+        let len = token.len().to_usize();
+        remaining = &remaining[len..];
+        Some(token)
+    })
+}
+
+/// Like `into_iter`, but for lexer functions built with `Diagnosed`
+/// tokens instead of a plain `Result<T, Error>`. Diagnostics collected
+/// along the way are pushed into `diagnostics`, tagged with the
+/// absolute offset they occurred at, as soon as their token is
+/// produced. The returned iterator stays a plain `(T, TextUnit)`
+/// stream, so it composes with `string_slices` like any other; the
+/// shared `Vec` is how callers that care about diagnostics inspect
+/// them, during or after iteration.
+pub fn into_iter_diagnosed<'a, F, T>(
+    input: &'a str,
+    diagnostics: &'a mut Vec<(Error, TextUnit)>,
+    mut f: F,
+) -> impl Iterator<Item = T::Token> + 'a
+where
+    T: Token + IntoDiagnostics,
+    F: (FnMut(&str) -> Option<T>) + 'a,
+{
+    let mut remaining = input;
+    let mut offset = TextUnit::from(0);
+    iter::from_fn(move || {
+        let token = f(remaining)?;
+        let len = token.len();
+        let (token, token_diagnostics) = token.into_diagnostics();
+        for (error, span) in token_diagnostics {
+            diagnostics.push((error, offset + span));
+        }
+        offset += len;
+        remaining = &remaining[len.to_usize()..];
+        Some(token)
+    })
+}
+
+/// Like `into_iter`, but yields each token together with its absolute
+/// `Span` within `input` rather than just its length, and emits one
+/// final zero-width `Eof` sentinel once the real input is exhausted.
+/// This gives parser authors stable absolute positions instead of
+/// only per-token lengths and reconstructed slices, plus a guaranteed
+/// end marker to drive recursive-descent loops.
+pub fn into_iter_spanned<'a, F, T>(input: &'a str, mut f: F) -> impl Iterator<Item = (T, Span)> + 'a
+where
+    T: Token + Eof,
+    F: (FnMut(&str) -> Option<T>) + 'a,
+{
+    let mut remaining = input;
+    let mut offset = TextUnit::from(0);
+    let end = TextUnit::of_str(input);
+    let mut finished = false;
+    iter::from_fn(move || {
+        if finished {
+            return None;
+        }
+        match f(remaining) {
+            Some(token) => {
+                let start = offset;
+                let len = token.len();
+                offset += len;
+                remaining = &remaining[len.to_usize()..];
+                Some((token, Span::new(start, offset)))
+            },
+            None => {
+                finished = true;
+                Some((T::eof(), Span::new(end, end)))
+            },
+        }
+    })
+}
+
 /// Wraps an iterator such as one produced by `into_iter` to returns
 /// an iterator of string references. This is unfortunately
 /// specialized for the type `(T, &str)` because it can't seem to be
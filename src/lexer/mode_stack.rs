@@ -0,0 +1,41 @@
+//! Context-sensitive lexer mode stack.
+
+/// A stack of lexer modes, letting a single lexer switch rule sets
+/// depending on context (e.g. default code vs. inside a string
+/// interpolation vs. inside a comment). The top of the stack is the
+/// currently active mode; child modes can override the rules of
+/// their parent without losing track of how to get back to it.
+#[derive(Debug, Clone)]
+pub struct ModeStack<M> {
+    stack: Vec<M>,
+}
+impl<M> ModeStack<M> {
+    /// Construct a new stack with a single initial mode, the one
+    /// active before anything is pushed.
+    pub fn new(initial: M) -> Self {
+        Self {
+            stack: vec![initial],
+        }
+    }
+
+    /// Push a new mode on top of the stack, making it the active one.
+    pub fn push(&mut self, mode: M) {
+        self.stack.push(mode);
+    }
+    /// Pop the active mode, returning to whatever was active before
+    /// it. The bottommost, initial mode can never be popped, since a
+    /// lexer must always have an active mode; popping it returns
+    /// `None` and leaves the stack untouched.
+    pub fn pop(&mut self) -> Option<M> {
+        if self.stack.len() > 1 {
+            self.stack.pop()
+        } else {
+            None
+        }
+    }
+    /// Return the currently active mode, the one on top of the
+    /// stack.
+    pub fn current(&self) -> &M {
+        self.stack.last().expect("ModeStack must never be empty")
+    }
+}
@@ -0,0 +1,266 @@
+//! Declarative pattern-matcher combinators built on top of `State`.
+//!
+//! Hand-written `peek`/`take_while` logic tends to get duplicated
+//! across every lexer. A `Pattern` is a small, reusable rule that can
+//! be composed with the combinators below into a single token rule,
+//! without needing a macro or codegen stage.
+
+use super::{Consumed, State};
+
+use std::fmt;
+use std::iter;
+use std::ops::RangeInclusive;
+
+/// Something that can be matched against a `State`, consuming as much
+/// input as it needs and reporting back how much it ate.
+///
+/// Returns `None`, leaving `state` untouched, if the pattern didn't
+/// match at all. Returns `Some(Consumed::zero())` if the pattern
+/// matched but happened to consume nothing (e.g. a successful `opt`
+/// whose inner pattern declined) -- this is distinct from `None` so
+/// that combinators like `seq` and `alt` can tell "matched, zero
+/// width" apart from "failed to match", and compose correctly with
+/// `opt`.
+pub trait Pattern {
+    /// Try to match this pattern against `state`.
+    fn try_match(&self, state: &mut State) -> Option<Consumed>;
+}
+
+/// Matches a literal string exactly. See `literal`.
+#[derive(Debug, Clone, Copy)]
+pub struct Literal<'p> {
+    text: &'p str,
+}
+impl<'p> Pattern for Literal<'p> {
+    fn try_match(&self, state: &mut State) -> Option<Consumed> {
+        if state.starts_with(self.text) {
+            Some(state.take(self.text))
+        } else {
+            None
+        }
+    }
+}
+/// Match a literal string exactly, or nothing at all.
+pub fn literal(text: &str) -> Literal<'_> {
+    Literal { text }
+}
+
+/// Matches a single character within an inclusive range. See
+/// `char_range`.
+#[derive(Debug, Clone)]
+pub struct CharRange {
+    range: RangeInclusive<char>,
+}
+impl Pattern for CharRange {
+    fn try_match(&self, state: &mut State) -> Option<Consumed> {
+        match state.peek() {
+            Ok(c) if self.range.contains(&c) => {
+                state.bump();
+                Some(iter::once(c).collect())
+            },
+            _ => None,
+        }
+    }
+}
+/// Match a single character that falls within `range`, e.g.
+/// `char_range('0'..='9')`.
+pub fn char_range(range: RangeInclusive<char>) -> CharRange {
+    CharRange { range }
+}
+
+/// Matches a single character meeting a predicate. See `pred`.
+pub struct Pred<F> {
+    predicate: F,
+}
+impl<F> fmt::Debug for Pred<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // `F` is an opaque closure and can't implement `Debug` itself.
+        f.debug_struct("Pred").finish()
+    }
+}
+impl<F> Pattern for Pred<F>
+where
+    F: Fn(char) -> bool,
+{
+    fn try_match(&self, state: &mut State) -> Option<Consumed> {
+        match state.peek() {
+            Ok(c) if (self.predicate)(c) => {
+                state.bump();
+                Some(iter::once(c).collect())
+            },
+            _ => None,
+        }
+    }
+}
+/// Match a single character meeting `predicate`.
+pub fn pred<F>(predicate: F) -> Pred<F>
+where
+    F: Fn(char) -> bool,
+{
+    Pred { predicate }
+}
+
+/// Matches `inner` one or more times. See `many1`.
+pub struct Many1<P> {
+    inner: P,
+}
+impl<P> fmt::Debug for Many1<P>
+where
+    P: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Many1").field("inner", &self.inner).finish()
+    }
+}
+impl<P> Pattern for Many1<P>
+where
+    P: Pattern,
+{
+    fn try_match(&self, state: &mut State) -> Option<Consumed> {
+        let mut total = Consumed::zero();
+        let mut matched_once = false;
+        loop {
+            match self.inner.try_match(state) {
+                Some(next) => {
+                    matched_once = true;
+                    let progressed = next.any();
+                    total = total + next;
+                    // An inner pattern that keeps matching without
+                    // consuming (e.g. `opt` of something absent)
+                    // would otherwise loop forever.
+                    if !progressed {
+                        break;
+                    }
+                },
+                None => break,
+            }
+        }
+        if matched_once {
+            Some(total)
+        } else {
+            None
+        }
+    }
+}
+/// Match `inner` repeatedly, as many times as possible. Fails cleanly
+/// (returning `None`, without having touched `state`) if `inner`
+/// didn't match even once.
+pub fn many1<P>(inner: P) -> Many1<P>
+where
+    P: Pattern,
+{
+    Many1 { inner }
+}
+
+/// Matches `inner` zero or one times. See `opt`.
+pub struct Opt<P> {
+    inner: P,
+}
+impl<P> fmt::Debug for Opt<P>
+where
+    P: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Opt").field("inner", &self.inner).finish()
+    }
+}
+impl<P> Pattern for Opt<P>
+where
+    P: Pattern,
+{
+    fn try_match(&self, state: &mut State) -> Option<Consumed> {
+        Some(self.inner.try_match(state).unwrap_or_else(Consumed::zero))
+    }
+}
+/// Match `inner` if possible, but never fail if it doesn't -- always
+/// succeeds, consuming nothing if `inner` didn't match.
+pub fn opt<P>(inner: P) -> Opt<P>
+where
+    P: Pattern,
+{
+    Opt { inner }
+}
+
+/// Matches a fixed sequence of patterns in order. See `seq`.
+#[derive(Clone, Copy)]
+pub struct Seq<'p> {
+    patterns: &'p [&'p dyn Pattern],
+}
+impl<'p> fmt::Debug for Seq<'p> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The individual `dyn Pattern`s aren't `Debug`, so just show
+        // how many there are.
+        f.debug_struct("Seq")
+            .field("patterns", &self.patterns.len())
+            .finish()
+    }
+}
+impl<'p> Pattern for Seq<'p> {
+    fn try_match(&self, state: &mut State) -> Option<Consumed> {
+        let checkpoint = state.checkpoint();
+        let mut total = Consumed::zero();
+        for pattern in self.patterns {
+            match pattern.try_match(state) {
+                Some(next) => total = total + next,
+                None => {
+                    state.restore(checkpoint);
+                    return None;
+                },
+            }
+        }
+        Some(total)
+    }
+}
+/// Match every pattern in `patterns`, one after another. If any of
+/// them fails to match, the whole sequence is rolled back and fails
+/// as a unit. A sub-pattern that legitimately matches nothing (e.g.
+/// `opt`) doesn't fail the sequence -- only an outright non-match
+/// does.
+pub fn seq<'p>(patterns: &'p [&'p dyn Pattern]) -> Seq<'p> {
+    Seq { patterns }
+}
+
+/// Matches the longest of a list of alternative patterns. See `alt`.
+#[derive(Clone, Copy)]
+pub struct Alt<'p> {
+    patterns: &'p [&'p dyn Pattern],
+}
+impl<'p> fmt::Debug for Alt<'p> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The individual `dyn Pattern`s aren't `Debug`, so just show
+        // how many there are.
+        f.debug_struct("Alt")
+            .field("patterns", &self.patterns.len())
+            .finish()
+    }
+}
+impl<'p> Pattern for Alt<'p> {
+    fn try_match(&self, state: &mut State) -> Option<Consumed> {
+        let checkpoint = state.checkpoint();
+        let mut best: Option<(Consumed, State)> = None;
+        for pattern in self.patterns {
+            let mut candidate = checkpoint;
+            let consumed = match pattern.try_match(&mut candidate) {
+                Some(consumed) => consumed,
+                None => continue,
+            };
+            let is_better = match best {
+                Some((best_consumed, _)) => consumed.bytes() > best_consumed.bytes(),
+                None => true,
+            };
+            if is_better {
+                best = Some((consumed, candidate));
+            }
+        }
+        best.map(|(consumed, candidate)| {
+            *state = candidate;
+            consumed
+        })
+    }
+}
+/// Try every pattern in `patterns` on a clone of the current state,
+/// and commit the longest match (the first one, in case of a tie).
+/// Fails if none of the alternatives match.
+pub fn alt<'p>(patterns: &'p [&'p dyn Pattern]) -> Alt<'p> {
+    Alt { patterns }
+}
@@ -27,3 +27,4 @@
 pub use rowan;
 
 pub mod lexer;
+pub mod parser;
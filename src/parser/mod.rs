@@ -0,0 +1,109 @@
+//! Bridges `lexer` token streams into rowan's lossless syntax tree.
+//!
+//! The crate is named rowan-tools and re-exports `rowan`, yet stopping
+//! at a `(TokenKind, &str)` iterator still leaves all the actual tree
+//! construction to the user. `TreeBuilder` wraps
+//! `rowan::GreenNodeBuilder` so parser authors only have to think in
+//! terms of tokens and nodes.
+
+use std::iter::Peekable;
+use std::marker::PhantomData;
+
+use rowan::{GreenNode, GreenNodeBuilder, SyntaxKind};
+
+use crate::lexer::Span;
+
+/// A point in an in-progress `TreeBuilder` that `start_node_at` can
+/// later use to wrap everything emitted since in a new node. Needed
+/// for left-associative binary operators, where the left-hand side is
+/// parsed (and emitted) before it's known to be part of a bigger
+/// node.
+#[derive(Debug, Clone, Copy)]
+pub struct Checkpoint(rowan::Checkpoint);
+
+/// Builds a rowan syntax tree out of a stream of tokens and node
+/// boundaries, translating any `K: Into<SyntaxKind>` kind into the
+/// `rowan::SyntaxKind` the underlying builder expects.
+#[derive(Debug)]
+pub struct TreeBuilder<K> {
+    builder: GreenNodeBuilder<'static>,
+    _kind: PhantomData<K>,
+}
+impl<K> TreeBuilder<K>
+where
+    K: Into<SyntaxKind>,
+{
+    /// Construct a new, empty tree builder.
+    pub fn new() -> Self {
+        Self {
+            builder: GreenNodeBuilder::new(),
+            _kind: PhantomData,
+        }
+    }
+
+    /// Emit a leaf token with the given kind and text.
+    pub fn token(&mut self, kind: K, text: &str) {
+        self.builder.token(kind.into(), text);
+    }
+    /// Start a new node; every `token`/`start_node` call until the
+    /// matching `finish_node` becomes a child of it.
+    pub fn start_node(&mut self, kind: K) {
+        self.builder.start_node(kind.into());
+    }
+    /// Finish the node started by the innermost unmatched
+    /// `start_node`/`start_node_at`.
+    pub fn finish_node(&mut self) {
+        self.builder.finish_node();
+    }
+
+    /// Save a checkpoint that `start_node_at` can later wrap a new
+    /// node around.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.builder.checkpoint())
+    }
+    /// Retroactively start a node around everything emitted since
+    /// `checkpoint`, e.g. to wrap an already-emitted left-hand side
+    /// in a binary operator node.
+    pub fn start_node_at(&mut self, checkpoint: Checkpoint, kind: K) {
+        self.builder.start_node_at(checkpoint.0, kind.into());
+    }
+
+    /// Given a peekable stream of `(token, span)` pairs (e.g. from
+    /// `lexer::into_iter_spanned`), drain and emit every token at the
+    /// front that `is_trivia` accepts as a plain leaf token, stopping
+    /// at the first significant one (left in place for the caller to
+    /// peek or consume). Meant to be called before each point in a
+    /// hand-written recursive-descent parser that needs to look past
+    /// trivia to decide what to parse next.
+    pub fn skip_trivia<I, T>(
+        &mut self,
+        input: &str,
+        tokens: &mut Peekable<I>,
+        is_trivia: impl Fn(&T) -> bool,
+    ) where
+        I: Iterator<Item = (T, Span)>,
+        T: Into<K>,
+    {
+        while let Some((token, _)) = tokens.peek() {
+            if !is_trivia(token) {
+                break;
+            }
+            let (token, span) = tokens.next().expect("just peeked");
+            let text = &input[span.start().to_usize()..span.end().to_usize()];
+            self.token(token.into(), text);
+        }
+    }
+
+    /// Finish building and return the resulting green tree.
+    pub fn finish(self) -> GreenNode {
+        self.builder.finish()
+    }
+}
+impl<K> Default for TreeBuilder<K>
+where
+    K: Into<SyntaxKind>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}